@@ -11,6 +11,12 @@ pub struct Args {
     #[arg(long, value_name = "CRON_SPEC")]
     pub cron: Option<String>,
 
+    #[arg(long)]
+    pub daemon: bool,
+
+    #[arg(long, value_name = "DURATION")]
+    pub interval: Option<String>,
+
     #[arg(short, long, default_value = "10")]
     pub warning: usize,
 
@@ -25,4 +31,27 @@ pub struct Args {
 
     #[arg(short = 'y', long)]
     pub yes: bool,
+
+    #[arg(long, value_name = "CONNSTRING")]
+    pub pg_config: Option<String>,
+
+    #[arg(long, value_name = "FILE")]
+    pub pg_ca_cert: Option<String>,
+
+    #[arg(long, value_name = "FILE")]
+    pub pg_client_identity: Option<String>,
+
+    /// Falls back to the `PG_CLIENT_IDENTITY_PASSWORD` environment variable
+    /// if not set, to avoid exposing the password via `ps`/`/proc`.
+    #[arg(long, value_name = "PASSWORD")]
+    pub pg_client_identity_password: Option<String>,
+
+    #[arg(long, value_name = "PATH")]
+    pub prometheus_textfile: Option<String>,
+
+    #[arg(long, value_name = "WEBHOOK")]
+    pub notify_url: Option<String>,
+
+    #[arg(long)]
+    pub notify_on_change: bool,
 }
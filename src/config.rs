@@ -1,3 +1,6 @@
+use anyhow::{bail, Result};
+use std::time::Duration;
+
 use crate::cli::Args;
 
 #[derive(Debug)]
@@ -9,6 +12,15 @@ pub struct Config {
     pub apply_security_updates: bool,
     pub apply_updates: bool,
     pub non_interactive: bool,
+    pub daemon: bool,
+    pub interval: Option<Duration>,
+    pub pg_config: Option<String>,
+    pub pg_ca_cert: Option<String>,
+    pub pg_client_identity: Option<String>,
+    pub pg_client_identity_password: Option<String>,
+    pub prometheus_textfile: Option<String>,
+    pub notify_url: Option<String>,
+    pub notify_on_change: bool,
 }
 
 impl Config {
@@ -18,6 +30,29 @@ impl Config {
             std::process::exit(1);
         }
 
+        if args.daemon && args.interval.is_none() {
+            eprintln!("Error: --daemon requires --interval");
+            std::process::exit(1);
+        }
+
+        if args.daemon && (args.update || args.security_update) && !args.yes {
+            eprintln!(
+                "Error: --daemon with --update/--security-update requires --yes (there is no terminal to confirm against)"
+            );
+            std::process::exit(1);
+        }
+
+        let interval = args
+            .interval
+            .as_deref()
+            .map(|spec| match parse_interval(spec) {
+                Ok(interval) => interval,
+                Err(e) => {
+                    eprintln!("Error: {:#}", e);
+                    std::process::exit(1);
+                }
+            });
+
         Self {
             lock_file: args.lock.clone(),
             cron_spec: args.cron.clone(),
@@ -26,6 +61,46 @@ impl Config {
             apply_security_updates: args.security_update,
             apply_updates: args.update,
             non_interactive: args.yes,
+            daemon: args.daemon,
+            interval,
+            pg_config: args.pg_config.clone(),
+            pg_ca_cert: args.pg_ca_cert.clone(),
+            pg_client_identity: args.pg_client_identity.clone(),
+            pg_client_identity_password: args
+                .pg_client_identity_password
+                .clone()
+                .or_else(|| std::env::var("PG_CLIENT_IDENTITY_PASSWORD").ok()),
+            prometheus_textfile: args.prometheus_textfile.clone(),
+            notify_url: args.notify_url.clone(),
+            notify_on_change: args.notify_on_change,
         }
     }
 }
+
+/// Parses an interval like `30s`, `5m`, `2h` or `1d`. A bare number is
+/// interpreted as whole seconds.
+fn parse_interval(spec: &str) -> Result<Duration> {
+    let spec = spec.trim();
+    let (value, unit) = match spec.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => spec.split_at(idx),
+        None => (spec, "s"),
+    };
+
+    let value: u64 = value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid interval value: {}", spec))?;
+
+    let seconds = match unit {
+        "s" => value,
+        "m" => value * 60,
+        "h" => value * 60 * 60,
+        "d" => value * 60 * 60 * 24,
+        _ => bail!("Invalid interval unit '{}' (expected s, m, h or d)", unit),
+    };
+
+    if seconds == 0 {
+        bail!("Interval must be greater than zero");
+    }
+
+    Ok(Duration::from_secs(seconds))
+}
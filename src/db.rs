@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use native_tls::{Certificate, Identity, TlsConnector};
+use postgres_native_tls::MakeTlsConnector;
+use tokio_postgres::config::SslMode;
+use tokio_postgres::types::Type;
+use tokio_postgres::{Client, NoTls};
+
+use crate::nagios::NagiosStatus;
+use crate::packagekit::UpdateInfo;
+
+/// Connection details for the optional history database, assembled from
+/// `--pg-config` and friends.
+pub struct PgConfig {
+    pub conn_string: String,
+    pub ca_cert: Option<String>,
+    pub client_identity: Option<String>,
+    pub client_identity_password: Option<String>,
+}
+
+pub struct HistoryStore {
+    client: Client,
+}
+
+impl HistoryStore {
+    pub async fn connect(config: &PgConfig) -> Result<Self> {
+        let pg_config: tokio_postgres::Config = config
+            .conn_string
+            .parse()
+            .context("Failed to parse --pg-config connection string")?;
+
+        let client = if pg_config.get_ssl_mode() == SslMode::Disable {
+            let (client, connection) = pg_config
+                .connect(NoTls)
+                .await
+                .context("Failed to connect to PostgreSQL")?;
+            spawn_connection(connection);
+            client
+        } else {
+            let connector = build_tls_connector(config)?;
+            let (client, connection) = pg_config
+                .connect(connector)
+                .await
+                .context("Failed to connect to PostgreSQL")?;
+            spawn_connection(connection);
+            client
+        };
+
+        let store = Self { client };
+        store.ensure_schema().await?;
+        Ok(store)
+    }
+
+    async fn ensure_schema(&self) -> Result<()> {
+        self.client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS update_runs (
+                    id BIGSERIAL PRIMARY KEY,
+                    host TEXT NOT NULL,
+                    checked_at TIMESTAMPTZ NOT NULL,
+                    total_count INTEGER NOT NULL,
+                    security_count INTEGER NOT NULL,
+                    status TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS update_packages (
+                    id BIGSERIAL PRIMARY KEY,
+                    run_id BIGINT NOT NULL REFERENCES update_runs(id),
+                    host TEXT NOT NULL,
+                    checked_at TIMESTAMPTZ NOT NULL,
+                    name TEXT NOT NULL,
+                    version TEXT NOT NULL,
+                    is_security BOOLEAN NOT NULL
+                );",
+            )
+            .await
+            .context("Failed to create history tables")?;
+
+        Ok(())
+    }
+
+    /// Records a completed check: one summary row in `update_runs`, plus
+    /// one row per package in `update_packages`, written with a single
+    /// binary COPY so a host with hundreds of pending updates doesn't
+    /// pay for hundreds of round-trips.
+    pub async fn record_run(
+        &mut self,
+        host: &str,
+        checked_at: DateTime<Local>,
+        updates: &[UpdateInfo],
+        security_count: usize,
+        status: NagiosStatus,
+    ) -> Result<()> {
+        let checked_at = checked_at.with_timezone(&Utc);
+
+        let row = self
+            .client
+            .query_one(
+                "INSERT INTO update_runs (host, checked_at, total_count, security_count, status)
+                 VALUES ($1, $2, $3, $4, $5) RETURNING id",
+                &[
+                    &host,
+                    &checked_at,
+                    &(updates.len() as i32),
+                    &(security_count as i32),
+                    &status.as_str(),
+                ],
+            )
+            .await
+            .context("Failed to insert update run summary")?;
+        let run_id: i64 = row.get(0);
+
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        let sink = self
+            .client
+            .copy_in(
+                "COPY update_packages (run_id, host, checked_at, name, version, is_security) \
+                 FROM STDIN BINARY",
+            )
+            .await
+            .context("Failed to start COPY for update_packages")?;
+
+        let types = [
+            Type::INT8,
+            Type::TEXT,
+            Type::TIMESTAMPTZ,
+            Type::TEXT,
+            Type::TEXT,
+            Type::BOOL,
+        ];
+        let writer = tokio_postgres::binary_copy::BinaryCopyInWriter::new(sink, &types);
+        tokio::pin!(writer);
+
+        for update in updates {
+            writer
+                .as_mut()
+                .write(&[
+                    &run_id,
+                    &host,
+                    &checked_at,
+                    &update.name,
+                    &update.version,
+                    &update.is_security,
+                ])
+                .await
+                .context("Failed to write update_packages row")?;
+        }
+
+        writer
+            .finish()
+            .await
+            .context("Failed to finish COPY for update_packages")?;
+
+        Ok(())
+    }
+}
+
+fn spawn_connection<T>(connection: tokio_postgres::Connection<tokio_postgres::Socket, T>)
+where
+    T: tokio_postgres::tls::TlsStream + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            eprintln!("PostgreSQL connection error: {}", e);
+        }
+    });
+}
+
+fn build_tls_connector(config: &PgConfig) -> Result<MakeTlsConnector> {
+    let mut builder = TlsConnector::builder();
+
+    if let Some(ca_path) = &config.ca_cert {
+        let pem = std::fs::read(ca_path).context("Failed to read --pg-ca-cert")?;
+        let cert = Certificate::from_pem(&pem).context("Failed to parse --pg-ca-cert as PEM")?;
+        builder.add_root_certificate(cert);
+    }
+
+    if let Some(identity_path) = &config.client_identity {
+        let pkcs12 = std::fs::read(identity_path).context("Failed to read --pg-client-identity")?;
+        let password = config.client_identity_password.as_deref().unwrap_or("");
+        let identity = Identity::from_pkcs12(&pkcs12, password)
+            .context("Failed to parse --pg-client-identity as PKCS#12")?;
+        builder.identity(identity);
+    }
+
+    let connector = builder.build().context("Failed to build TLS connector")?;
+    Ok(MakeTlsConnector::new(connector))
+}
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local};
 use fs2::FileExt;
+use serde::{Deserialize, Serialize};
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::Path;
@@ -9,6 +10,17 @@ pub struct FileLock {
     file: File,
 }
 
+/// Small JSON blob stored in the lock file alongside the exclusive-run
+/// guard: the last-run timestamp used for `--cron` gating, and the last
+/// security-update count used for `--notify-on-change` deduplication.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LockState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_security_count: Option<usize>,
+}
+
 impl FileLock {
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
         let file = OpenOptions::new()
@@ -30,19 +42,45 @@ impl FileLock {
         }
     }
 
-    pub fn read_timestamp(&mut self) -> Result<Option<DateTime<Local>>> {
+    fn read_state(&mut self) -> Result<LockState> {
         let mut contents = String::new();
         self.file.seek(SeekFrom::Start(0))?;
         self.file.read_to_string(&mut contents)?;
 
+        let contents = contents.trim();
         if contents.is_empty() {
-            return Ok(None);
+            return Ok(LockState::default());
         }
 
+        if let Ok(state) = serde_json::from_str::<LockState>(contents) {
+            return Ok(state);
+        }
+
+        // Older lock files hold a bare Unix timestamp instead of the JSON
+        // blob; keep reading those so upgrading doesn't reset the gate.
         let timestamp = contents
-            .trim()
             .parse::<i64>()
-            .context("Failed to parse timestamp")?;
+            .context("Failed to parse lock file contents")?;
+
+        Ok(LockState {
+            timestamp: Some(timestamp),
+            last_security_count: None,
+        })
+    }
+
+    fn write_state(&mut self, state: &LockState) -> Result<()> {
+        let json = serde_json::to_string(state).context("Failed to serialize lock state")?;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.set_len(0)?;
+        write!(self.file, "{}", json)?;
+        self.file.flush()?;
+        Ok(())
+    }
+
+    pub fn read_timestamp(&mut self) -> Result<Option<DateTime<Local>>> {
+        let Some(timestamp) = self.read_state()?.timestamp else {
+            return Ok(None);
+        };
 
         let datetime = DateTime::from_timestamp(timestamp, 0)
             .context("Invalid timestamp")?
@@ -52,11 +90,19 @@ impl FileLock {
     }
 
     pub fn write_timestamp(&mut self, time: DateTime<Local>) -> Result<()> {
-        self.file.seek(SeekFrom::Start(0))?;
-        self.file.set_len(0)?;
-        write!(self.file, "{}", time.timestamp())?;
-        self.file.flush()?;
-        Ok(())
+        let mut state = self.read_state()?;
+        state.timestamp = Some(time.timestamp());
+        self.write_state(&state)
+    }
+
+    pub fn read_last_security_count(&mut self) -> Result<Option<usize>> {
+        Ok(self.read_state()?.last_security_count)
+    }
+
+    pub fn write_last_security_count(&mut self, count: usize) -> Result<()> {
+        let mut state = self.read_state()?;
+        state.last_security_count = Some(count);
+        self.write_state(&state)
     }
 }
 
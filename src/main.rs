@@ -1,9 +1,12 @@
 mod cli;
 mod config;
 mod cron;
+mod db;
 mod lock;
 mod nagios;
+mod notify;
 mod packagekit;
+mod prometheus;
 
 use anyhow::{Context, Result};
 use chrono::Local;
@@ -11,8 +14,12 @@ use clap::Parser;
 use futures::stream::StreamExt;
 use signal_hook::consts::signal::*;
 use signal_hook_tokio::Signals;
-use std::sync::Arc;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Notify;
+use tokio_stream::wrappers::IntervalStream;
 
 use crate::cli::Args;
 use crate::config::Config;
@@ -26,7 +33,9 @@ async fn main() -> Result<()> {
     let config = Config::from_args(&args);
 
     let running = Arc::new(AtomicBool::new(true));
+    let shutdown = Arc::new(Notify::new());
     let r = running.clone();
+    let shutdown_signal = shutdown.clone();
 
     let mut signals = Signals::new([SIGTERM, SIGINT, SIGQUIT, SIGHUP])?;
     tokio::spawn(async move {
@@ -35,13 +44,18 @@ async fn main() -> Result<()> {
                 SIGTERM | SIGINT | SIGQUIT => {
                     eprintln!("Received signal {}, terminating...", signal);
                     r.store(false, Ordering::Relaxed);
+                    shutdown_signal.notify_waiters();
                 }
                 _ => {}
             }
         }
     });
 
-    let _lock = if let Some(lock_path) = &config.lock_file {
+    if config.daemon {
+        return run_daemon(config, running, shutdown).await;
+    }
+
+    let mut lock = if let Some(lock_path) = &config.lock_file {
         let mut lock = FileLock::new(lock_path)?;
 
         if !lock.try_lock()? {
@@ -76,12 +90,24 @@ async fn main() -> Result<()> {
         None
     };
 
-    match run_update_check(config, running).await {
+    let mut phases = prometheus::PhaseHistograms::default();
+
+    match run_update_check(&config, &running, &mut phases, lock.as_mut()).await {
         Ok(output) => {
             println!("{}", output);
             std::process::exit(output.status.exit_code());
         }
         Err(e) => {
+            maybe_notify(
+                &config,
+                lock.as_mut(),
+                NagiosStatus::Critical,
+                0,
+                0,
+                &[],
+                false,
+            )
+            .await;
             let output = NagiosOutput {
                 status: NagiosStatus::Critical,
                 message: format!("An error occurred: {:#}", e),
@@ -93,17 +119,101 @@ async fn main() -> Result<()> {
     }
 }
 
-async fn run_update_check(config: Config, running: Arc<AtomicBool>) -> Result<NagiosOutput> {
+/// Keeps the process alive and re-runs `run_update_check` on a fixed
+/// cadence instead of relying on an external cron/systemd timer. Exits
+/// promptly when a shutdown signal arrives, and otherwise respects the
+/// same `--cron` window as single-shot mode by consulting the lock
+/// file's stored timestamp before each cycle.
+async fn run_daemon(config: Config, running: Arc<AtomicBool>, shutdown: Arc<Notify>) -> Result<()> {
+    let interval = config.interval.context("Daemon mode requires --interval")?;
+
+    eprintln!("Starting daemon mode, checking every {:?}", interval);
+
+    let mut lock = if let Some(lock_path) = &config.lock_file {
+        let mut lock = FileLock::new(lock_path)?;
+        if !lock.try_lock()? {
+            eprintln!("Another instance is already running, exiting.");
+            return Ok(());
+        }
+        Some(lock)
+    } else {
+        None
+    };
+
+    let mut timer = tokio::time::interval(interval);
+    timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut ticks = IntervalStream::new(timer);
+    let mut phases = prometheus::PhaseHistograms::default();
+
+    while running.load(Ordering::Relaxed) {
+        tokio::select! {
+            _ = shutdown.notified() => break,
+            tick = ticks.next() => {
+                if tick.is_none() {
+                    break;
+                }
+            }
+        }
+
+        if !running.load(Ordering::Relaxed) {
+            break;
+        }
+
+        if let Some(lock) = lock.as_mut() {
+            if let Some(cron_spec) = &config.cron_spec {
+                let now = Local::now();
+
+                if let Some(last_run) = lock.read_timestamp()? {
+                    if !cron::should_run(cron_spec, last_run, now)? {
+                        eprintln!("Outside of cron window, skipping this cycle");
+                        continue;
+                    }
+                }
+
+                lock.write_timestamp(now)?;
+            }
+        }
+
+        match run_update_check(&config, &running, &mut phases, lock.as_mut()).await {
+            Ok(output) => println!("{}", output),
+            Err(e) => {
+                maybe_notify(
+                    &config,
+                    lock.as_mut(),
+                    NagiosStatus::Critical,
+                    0,
+                    0,
+                    &[],
+                    false,
+                )
+                .await;
+                eprintln!("An error occurred: {:#}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_update_check(
+    config: &Config,
+    running: &Arc<AtomicBool>,
+    phases: &mut prometheus::PhaseHistograms,
+    mut lock: Option<&mut FileLock>,
+) -> Result<NagiosOutput> {
     let pm = PackageManager::new().await?;
 
     if running.load(Ordering::Relaxed) {
         eprintln!("Refreshing package cache...");
-        pm.refresh_cache()
-            .await
-            .context("Failed to refresh package cache")?;
+        let start = Instant::now();
+        let result = pm.refresh_cache().await;
+        phases.refresh.observe(start.elapsed());
+        result.context("Failed to refresh package cache")?;
     }
 
     if !running.load(Ordering::Relaxed) {
+        emit_metrics(config, phases, 0, 0, false);
+        maybe_notify(config, lock, NagiosStatus::Critical, 0, 0, &[], false).await;
         return Ok(NagiosOutput {
             status: NagiosStatus::Critical,
             message: "Operation cancelled".to_string(),
@@ -112,10 +222,15 @@ async fn run_update_check(config: Config, running: Arc<AtomicBool>) -> Result<Na
     }
 
     eprintln!("Getting available updates...");
-    let updates = pm.get_updates().await.context("Failed to get updates")?;
+    let start = Instant::now();
+    let updates = pm.get_updates().await;
+    phases.get_updates.observe(start.elapsed());
+    let updates = updates.context("Failed to get updates")?;
 
     if updates.is_empty() {
         eprintln!("Everything is up to date.");
+        emit_metrics(config, phases, 0, 0, true);
+        maybe_notify(config, lock, NagiosStatus::Ok, 0, 0, &[], false).await;
         return Ok(NagiosOutput {
             status: NagiosStatus::Ok,
             message: "Everything is up to date".to_string(),
@@ -124,10 +239,10 @@ async fn run_update_check(config: Config, running: Arc<AtomicBool>) -> Result<Na
     }
 
     eprintln!("Getting update details...");
-    let detailed_updates = pm
-        .get_update_details(&updates)
-        .await
-        .context("Failed to get update details")?;
+    let start = Instant::now();
+    let detailed_updates = pm.get_update_details(&updates).await;
+    phases.get_details.observe(start.elapsed());
+    let detailed_updates = detailed_updates.context("Failed to get update details")?;
 
     let total_count = detailed_updates.len();
     let mut security_count = 0;
@@ -167,6 +282,8 @@ async fn run_update_check(config: Config, running: Arc<AtomicBool>) -> Result<Na
         eprintln!("(none)");
     }
 
+    let mut applied = false;
+
     if config.apply_updates || config.apply_security_updates {
         let updates_to_apply = if config.apply_updates {
             all_updates
@@ -176,6 +293,17 @@ async fn run_update_check(config: Config, running: Arc<AtomicBool>) -> Result<Na
 
         if !updates_to_apply.is_empty() {
             if !config.non_interactive && !prompt_confirmation()? {
+                emit_metrics(config, phases, total_count, security_count, false);
+                maybe_notify(
+                    config,
+                    lock,
+                    NagiosStatus::Critical,
+                    total_count,
+                    security_count,
+                    &detailed_updates,
+                    false,
+                )
+                .await;
                 return Ok(NagiosOutput {
                     status: NagiosStatus::Critical,
                     message: "Cancelled by user".to_string(),
@@ -187,6 +315,7 @@ async fn run_update_check(config: Config, running: Arc<AtomicBool>) -> Result<Na
             pm.apply_updates(&updates_to_apply)
                 .await
                 .context("Failed to apply updates")?;
+            applied = true;
         }
     }
 
@@ -198,6 +327,24 @@ async fn run_update_check(config: Config, running: Arc<AtomicBool>) -> Result<Na
         NagiosStatus::Ok
     };
 
+    if config.pg_config.is_some() {
+        if let Err(e) = persist_history(config, &detailed_updates, security_count, status).await {
+            eprintln!("Failed to record update history: {:#}", e);
+        }
+    }
+
+    emit_metrics(config, phases, total_count, security_count, true);
+    maybe_notify(
+        config,
+        lock,
+        status,
+        total_count,
+        security_count,
+        &detailed_updates,
+        applied,
+    )
+    .await;
+
     Ok(NagiosOutput {
         status,
         message: format!(
@@ -211,6 +358,121 @@ async fn run_update_check(config: Config, running: Arc<AtomicBool>) -> Result<Na
     })
 }
 
+/// Writes the node_exporter textfile collector output for this run. A
+/// no-op unless `--prometheus-textfile` was supplied.
+fn emit_metrics(
+    config: &Config,
+    phases: &prometheus::PhaseHistograms,
+    total_updates: usize,
+    security_updates: usize,
+    check_success: bool,
+) {
+    let Some(path) = &config.prometheus_textfile else {
+        return;
+    };
+
+    let result = prometheus::write_textfile(
+        Path::new(path),
+        total_updates,
+        security_updates,
+        check_success,
+        Local::now().timestamp(),
+        phases,
+    );
+
+    if let Err(e) = result {
+        eprintln!("Failed to write Prometheus textfile: {:#}", e);
+    }
+}
+
+/// Posts a notification for this run. A no-op unless `--notify-url` was
+/// supplied. Always fires on `Critical`; otherwise, with
+/// `--notify-on-change`, it's skipped when the security-update count
+/// matches the value stored in the lock file from the previous run.
+async fn maybe_notify(
+    config: &Config,
+    mut lock: Option<&mut FileLock>,
+    status: NagiosStatus,
+    total_updates: usize,
+    security_updates: usize,
+    packages: &[packagekit::UpdateInfo],
+    applied: bool,
+) {
+    let Some(url) = &config.notify_url else {
+        return;
+    };
+
+    if config.notify_on_change && status != NagiosStatus::Critical {
+        let unchanged = lock
+            .as_deref_mut()
+            .and_then(|l| l.read_last_security_count().ok().flatten())
+            .is_some_and(|last| last == security_updates);
+
+        if unchanged {
+            return;
+        }
+    }
+
+    // Only remember the count from a run that actually measured the system;
+    // a `Critical` here can mean "failed before we could check" as easily as
+    // "too many updates", and persisting 0 in the former case would make the
+    // next real measurement look like a spurious change.
+    if status != NagiosStatus::Critical {
+        if let Some(lock) = lock {
+            if let Err(e) = lock.write_last_security_count(security_updates) {
+                eprintln!("Failed to persist notification state: {:#}", e);
+            }
+        }
+    }
+
+    let summary = notify::RunSummary {
+        status: status.as_str().to_string(),
+        total_updates,
+        security_updates,
+        packages: packages
+            .iter()
+            .map(|u| notify::PackageSummary {
+                name: u.name.clone(),
+                version: u.version.clone(),
+                is_security: u.is_security,
+            })
+            .collect(),
+        applied,
+    };
+
+    let transport = notify::Transport::Webhook(url.clone());
+    if let Err(e) = transport.send(&summary).await {
+        eprintln!("Failed to send notification: {:#}", e);
+    }
+}
+
+/// Connects to the configured PostgreSQL history database and records this
+/// run's results. A no-op unless `--pg-config` was supplied.
+async fn persist_history(
+    config: &Config,
+    updates: &[packagekit::UpdateInfo],
+    security_count: usize,
+    status: NagiosStatus,
+) -> Result<()> {
+    let Some(conn_string) = &config.pg_config else {
+        return Ok(());
+    };
+
+    let pg_config = db::PgConfig {
+        conn_string: conn_string.clone(),
+        ca_cert: config.pg_ca_cert.clone(),
+        client_identity: config.pg_client_identity.clone(),
+        client_identity_password: config.pg_client_identity_password.clone(),
+    };
+
+    let mut store = db::HistoryStore::connect(&pg_config).await?;
+    let host = gethostname::gethostname().to_string_lossy().into_owned();
+
+    store
+        .record_run(&host, Local::now(), updates, security_count, status)
+        .await
+}
+
 fn prompt_confirmation() -> Result<bool> {
     use std::io::{self, Write};
 
@@ -0,0 +1,51 @@
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageSummary {
+    pub name: String,
+    pub version: String,
+    pub is_security: bool,
+}
+
+/// Everything a notification needs to describe one completed run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub status: String,
+    pub total_updates: usize,
+    pub security_updates: usize,
+    pub packages: Vec<PackageSummary>,
+    pub applied: bool,
+}
+
+/// A delivery target for run notifications. Only a generic JSON webhook is
+/// implemented today; provider-specific embed formats (Slack/Discord/Teams)
+/// can be added as further variants without touching call sites.
+pub enum Transport {
+    Webhook(String),
+}
+
+impl Transport {
+    pub async fn send(&self, summary: &RunSummary) -> Result<()> {
+        match self {
+            Transport::Webhook(url) => send_webhook(url, summary).await,
+        }
+    }
+}
+
+async fn send_webhook(url: &str, summary: &RunSummary) -> Result<()> {
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(url)
+        .json(summary)
+        .send()
+        .await
+        .context("Failed to send webhook notification")?;
+
+    if !response.status().is_success() {
+        bail!("Webhook endpoint returned HTTP {}", response.status());
+    }
+
+    Ok(())
+}
@@ -1,10 +1,9 @@
-use anyhow::{Context, Result, bail};
+use anyhow::{bail, Context, Result};
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use zbus::{Connection, proxy};
+use std::time::Duration;
+use zbus::{proxy, Connection};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateInfo {
@@ -17,6 +16,10 @@ pub struct UpdateInfo {
 const PK_FILTER_ENUM_NONE: u64 = 0;
 const PK_TRANSACTION_FLAG_ENUM_ONLY_TRUSTED: u64 = 1 << 1;
 
+/// Upper bound on how long we'll wait for a PackageKit transaction to emit
+/// `Finished` before giving up and failing the check.
+const TRANSACTION_TIMEOUT: Duration = Duration::from_secs(300);
+
 #[proxy(
     interface = "org.freedesktop.PackageKit",
     default_service = "org.freedesktop.PackageKit",
@@ -98,24 +101,28 @@ impl PackageManager {
             .await
             .context("Failed to create transaction proxy")?;
 
-        let finished = Arc::new(Mutex::new(false));
-        let finished_clone = finished.clone();
-
         let mut finished_stream = transaction.receive_finished().await?;
-        tokio::spawn(async move {
-            while (finished_stream.next().await).is_some() {
-                *finished_clone.lock().await = true;
-            }
-        });
+        let mut error_stream = transaction.receive_error_code().await?;
 
         transaction
             .refresh_cache(true)
             .await
             .context("Failed to refresh cache")?;
 
-        while !*finished.lock().await {
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        }
+        tokio::time::timeout(TRANSACTION_TIMEOUT, async {
+            loop {
+                tokio::select! {
+                    Some(signal) = error_stream.next() => {
+                        if let Ok(args) = signal.args() {
+                            bail!("Cache refresh failed: {}", args.details);
+                        }
+                    }
+                    Some(_) = finished_stream.next() => return Ok(()),
+                }
+            }
+        })
+        .await
+        .context("Timed out waiting for cache refresh to finish")??;
 
         Ok(())
     }
@@ -138,38 +145,37 @@ impl PackageManager {
             .await
             .context("Failed to create transaction proxy")?;
 
-        let packages = Arc::new(Mutex::new(Vec::new()));
-        let packages_clone = packages.clone();
-        let finished = Arc::new(Mutex::new(false));
-        let finished_clone = finished.clone();
-
         let mut package_stream = transaction.receive_package().await?;
-        tokio::spawn(async move {
-            while let Some(signal) = package_stream.next().await {
-                if let Ok(args) = signal.args() {
-                    packages_clone.lock().await.push(args.package_id);
-                }
-            }
-        });
-
         let mut finished_stream = transaction.receive_finished().await?;
-        tokio::spawn(async move {
-            while (finished_stream.next().await).is_some() {
-                *finished_clone.lock().await = true;
-            }
-        });
+        let mut error_stream = transaction.receive_error_code().await?;
 
         transaction
             .get_updates(PK_FILTER_ENUM_NONE)
             .await
             .context("Failed to get updates")?;
 
-        while !*finished.lock().await {
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        }
+        let packages = tokio::time::timeout(TRANSACTION_TIMEOUT, async {
+            let mut packages = Vec::new();
+            loop {
+                tokio::select! {
+                    Some(signal) = package_stream.next() => {
+                        if let Ok(args) = signal.args() {
+                            packages.push(args.package_id);
+                        }
+                    }
+                    Some(signal) = error_stream.next() => {
+                        if let Ok(args) = signal.args() {
+                            bail!("Failed to get updates: {}", args.details);
+                        }
+                    }
+                    Some(_) = finished_stream.next() => return Ok(packages),
+                }
+            }
+        })
+        .await
+        .context("Timed out waiting for update list to finish")??;
 
-        let result = packages.lock().await.clone();
-        Ok(result)
+        Ok(packages)
     }
 
     pub async fn get_update_details(&self, package_ids: &[String]) -> Result<Vec<UpdateInfo>> {
@@ -194,33 +200,9 @@ impl PackageManager {
             .await
             .context("Failed to create transaction proxy")?;
 
-        let details = Arc::new(Mutex::new(HashMap::new()));
-        let details_clone = details.clone();
-        let finished = Arc::new(Mutex::new(false));
-        let finished_clone = finished.clone();
-
         let mut update_detail_stream = transaction.receive_update_detail().await?;
-        tokio::spawn(async move {
-            while let Some(signal) = update_detail_stream.next().await {
-                if let Ok(args) = signal.args() {
-                    let is_security = !args.cve_urls.is_empty()
-                        || args.update_text.contains("CVE-")
-                        || args.changelog.contains("CVE-");
-
-                    details_clone
-                        .lock()
-                        .await
-                        .insert(args.package_id, is_security);
-                }
-            }
-        });
-
         let mut finished_stream = transaction.receive_finished().await?;
-        tokio::spawn(async move {
-            while (finished_stream.next().await).is_some() {
-                *finished_clone.lock().await = true;
-            }
-        });
+        let mut error_stream = transaction.receive_error_code().await?;
 
         let package_refs: Vec<&str> = package_ids.iter().map(|s| s.as_str()).collect();
         transaction
@@ -228,13 +210,32 @@ impl PackageManager {
             .await
             .context("Failed to get update details")?;
 
-        while !*finished.lock().await {
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        }
+        let details_map = tokio::time::timeout(TRANSACTION_TIMEOUT, async {
+            let mut details_map = HashMap::new();
+            loop {
+                tokio::select! {
+                    Some(signal) = update_detail_stream.next() => {
+                        if let Ok(args) = signal.args() {
+                            let is_security = !args.cve_urls.is_empty()
+                                || args.update_text.contains("CVE-")
+                                || args.changelog.contains("CVE-");
+
+                            details_map.insert(args.package_id, is_security);
+                        }
+                    }
+                    Some(signal) = error_stream.next() => {
+                        if let Ok(args) = signal.args() {
+                            bail!("Failed to get update details: {}", args.details);
+                        }
+                    }
+                    Some(_) = finished_stream.next() => return Ok(details_map),
+                }
+            }
+        })
+        .await
+        .context("Timed out waiting for update details to finish")??;
 
-        let details_map = details.lock().await;
         let mut results = Vec::new();
-
         for package_id in package_ids {
             let is_security = details_map.get(package_id).copied().unwrap_or(false);
 
@@ -268,26 +269,8 @@ impl PackageManager {
             .await
             .context("Failed to create transaction proxy")?;
 
-        let finished = Arc::new(Mutex::new(false));
-        let finished_clone = finished.clone();
-        let error = Arc::new(Mutex::new(None));
-        let error_clone = error.clone();
-
         let mut finished_stream = transaction.receive_finished().await?;
-        tokio::spawn(async move {
-            while (finished_stream.next().await).is_some() {
-                *finished_clone.lock().await = true;
-            }
-        });
-
         let mut error_stream = transaction.receive_error_code().await?;
-        tokio::spawn(async move {
-            while let Some(signal) = error_stream.next().await {
-                if let Ok(args) = signal.args() {
-                    *error_clone.lock().await = Some(args.details);
-                }
-            }
-        });
 
         let package_ids: Vec<&str> = updates.iter().map(|u| u.package_id.as_str()).collect();
         transaction
@@ -295,13 +278,20 @@ impl PackageManager {
             .await
             .context("Failed to update packages")?;
 
-        while !*finished.lock().await {
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        }
-
-        if let Some(error_msg) = error.lock().await.as_ref() {
-            bail!("Package update failed: {}", error_msg);
-        }
+        tokio::time::timeout(TRANSACTION_TIMEOUT, async {
+            loop {
+                tokio::select! {
+                    Some(signal) = error_stream.next() => {
+                        if let Ok(args) = signal.args() {
+                            bail!("Package update failed: {}", args.details);
+                        }
+                    }
+                    Some(_) = finished_stream.next() => return Ok(()),
+                }
+            }
+        })
+        .await
+        .context("Timed out waiting for package update to finish")??;
 
         Ok(())
     }
@@ -0,0 +1,178 @@
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+/// Fixed exponential bucket upper bounds (seconds) for the phase duration
+/// histogram.
+const BUCKETS: [f64; 8] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Accumulates observations of a single PackageKit phase's duration into
+/// Prometheus-style cumulative buckets.
+#[derive(Debug, Clone, Default)]
+pub struct PhaseHistogram {
+    bucket_counts: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl PhaseHistogram {
+    pub fn observe(&mut self, duration: Duration) {
+        let value = duration.as_secs_f64();
+
+        for (count, bound) in self.bucket_counts.iter_mut().zip(BUCKETS) {
+            if value <= bound {
+                *count += 1;
+            }
+        }
+
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, phase: &str) {
+        for (bound, count) in BUCKETS.iter().zip(self.bucket_counts.iter()) {
+            writeln!(
+                out,
+                "system_updates_phase_duration_seconds_bucket{{phase=\"{}\",le=\"{}\"}} {}",
+                phase, bound, count
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "system_updates_phase_duration_seconds_bucket{{phase=\"{}\",le=\"+Inf\"}} {}",
+            phase, self.count
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "system_updates_phase_duration_seconds_sum{{phase=\"{}\"}} {}",
+            phase, self.sum
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "system_updates_phase_duration_seconds_count{{phase=\"{}\"}} {}",
+            phase, self.count
+        )
+        .unwrap();
+    }
+}
+
+/// Per-phase duration histograms for one process's lifetime. In `--daemon`
+/// mode these accumulate across cycles; in single-shot mode each process
+/// reports at most one observation per phase (a phase the run never
+/// reaches, such as fetching details when there are no updates, has none).
+#[derive(Debug, Clone, Default)]
+pub struct PhaseHistograms {
+    pub refresh: PhaseHistogram,
+    pub get_updates: PhaseHistogram,
+    pub get_details: PhaseHistogram,
+}
+
+/// Renders the current state as a node_exporter textfile collector file and
+/// atomically replaces `path` with it: write to a temp file in the same
+/// directory, then rename, so the collector never reads a half-written
+/// file.
+pub fn write_textfile(
+    path: &Path,
+    total_updates: usize,
+    security_updates: usize,
+    check_success: bool,
+    timestamp: i64,
+    phases: &PhaseHistograms,
+) -> Result<()> {
+    let body = render(
+        total_updates,
+        security_updates,
+        check_success,
+        timestamp,
+        phases,
+    );
+
+    let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+    let dir = dir.unwrap_or_else(|| Path::new("."));
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("metrics.prom");
+    let tmp_path = dir.join(format!(".{}.tmp", file_name));
+
+    std::fs::write(&tmp_path, body).context("Failed to write temporary Prometheus textfile")?;
+    std::fs::rename(&tmp_path, path).context("Failed to rename Prometheus textfile into place")?;
+
+    Ok(())
+}
+
+fn render(
+    total_updates: usize,
+    security_updates: usize,
+    check_success: bool,
+    timestamp: i64,
+    phases: &PhaseHistograms,
+) -> String {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "# HELP system_updates_total Number of pending package updates."
+    )
+    .unwrap();
+    writeln!(out, "# TYPE system_updates_total gauge").unwrap();
+    writeln!(out, "system_updates_total {}", total_updates).unwrap();
+
+    writeln!(
+        out,
+        "# HELP system_updates_security_total Number of pending security package updates."
+    )
+    .unwrap();
+    writeln!(out, "# TYPE system_updates_security_total gauge").unwrap();
+    writeln!(out, "system_updates_security_total {}", security_updates).unwrap();
+
+    writeln!(
+        out,
+        "# HELP system_updates_check_success Whether the last check completed successfully (1) or not (0)."
+    )
+    .unwrap();
+    writeln!(out, "# TYPE system_updates_check_success gauge").unwrap();
+    writeln!(
+        out,
+        "system_updates_check_success {}",
+        if check_success { 1 } else { 0 }
+    )
+    .unwrap();
+
+    writeln!(
+        out,
+        "# HELP system_updates_last_check_timestamp_seconds Unix timestamp of the last completed check."
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "# TYPE system_updates_last_check_timestamp_seconds gauge"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "system_updates_last_check_timestamp_seconds {}",
+        timestamp
+    )
+    .unwrap();
+
+    writeln!(
+        out,
+        "# HELP system_updates_phase_duration_seconds Duration of each PackageKit check phase."
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "# TYPE system_updates_phase_duration_seconds histogram"
+    )
+    .unwrap();
+    phases.refresh.render(&mut out, "refresh");
+    phases.get_updates.render(&mut out, "get_updates");
+    phases.get_details.render(&mut out, "get_details");
+
+    out
+}